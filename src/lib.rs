@@ -4,9 +4,58 @@ pub mod mmbnlc;
 use crate::mmbnlc::*;
 use mlua::prelude::*;
 
-static mut HOOKS: Vec<ilhook::x64::HookPoint> = Vec::new();
+/// One hook installed via [`hook_direct`], tracked so it can be reverted.
+struct RegisteredHook {
+    /// The address the hook was installed at, and its opaque handle.
+    addr: usize,
+    /// The original code displaced by the hook, and the means to restore it.
+    point: ilhook::x64::HookPoint,
+}
+
+/// Tracks every hook installed via `hook_direct`, so that any of them can be
+/// reverted at a later point instead of leaking for the lifetime of the
+/// process.
+struct HookRegistry {
+    hooks: Vec<RegisteredHook>,
+}
+
+impl HookRegistry {
+    const fn new() -> Self {
+        Self { hooks: Vec::new() }
+    }
+
+    /// Records a freshly installed hook and returns its handle.
+    fn register(&mut self, addr: usize, point: ilhook::x64::HookPoint) -> usize {
+        self.hooks.push(RegisteredHook { addr, point });
+        addr
+    }
+
+    /// Restores the original bytes at `handle` and forgets about it.
+    /// Returns whether a hook with that handle was found.
+    fn unhook(&mut self, handle: usize) -> bool {
+        match self.hooks.iter().position(|hook| hook.addr == handle) {
+            Some(index) => {
+                let hook = self.hooks.remove(index);
+                unsafe { ilhook::x64::unhook(hook.point) }
+                    .expect(format!("Failed to unhook {handle:#X}!").as_str());
+                true
+            }
+            None => false,
+        }
+    }
 
-fn hook_direct(addr: usize, func: ilhook::x64::JmpToRetRoutine, user_data: usize) {
+    /// Restores the original bytes at every tracked hook.
+    fn unhook_all(&mut self) {
+        for hook in self.hooks.drain(..) {
+            unsafe { ilhook::x64::unhook(hook.point) }
+                .expect(format!("Failed to unhook {:#X}!", hook.addr).as_str());
+        }
+    }
+}
+
+static mut HOOKS: HookRegistry = HookRegistry::new();
+
+fn hook_direct(addr: usize, func: ilhook::x64::JmpToRetRoutine, user_data: usize) -> usize {
     let hooker = ilhook::x64::Hooker::new(
         addr,
         ilhook::x64::HookType::JmpToRet(func),
@@ -17,24 +66,39 @@ fn hook_direct(addr: usize, func: ilhook::x64::JmpToRetRoutine, user_data: usize
     let hook = unsafe { hooker.hook() };
     let hook = hook.expect(format!("Failed to hook {addr:#X}!").as_str());
 
-    unsafe { &mut HOOKS }.push(hook);
+    unsafe { &mut HOOKS }.register(addr, hook)
 }
 
-fn hook_search(
-    region: &[u8],
-    what: &str,
+/// One signature to search for in [`hook_search`]: the query string, how
+/// many of its matches to hook (at most), and the hook callback to install
+/// at each one.
+struct HookSignature<'a> {
+    what: &'a str,
     n: usize,
     func: ilhook::x64::JmpToRetRoutine,
-) -> Result<(), ()> {
-    let query = memsearch::Query::build(what).expect("query string should be valid");
-    let matches = query
-        .iter_matches_in(region.as_ptr() as usize, region.len())
-        .take(n);
-    for addr in matches {
+}
+
+/// Searches `region` for every signature in `signatures` in a single pass
+/// via [`memsearch::MultiQuery`], and hooks each match with its associated
+/// callback. This keeps adding further signatures cheap, since they all
+/// share the same scan over `region` instead of rescanning it each time.
+fn hook_search(region: &[u8], signatures: &[HookSignature]) -> Result<Vec<usize>, ()> {
+    let multi = memsearch::MultiQuery::build(
+        &signatures.iter().map(|sig| sig.what).collect::<Vec<_>>()
+    ).expect("query strings should be valid");
+
+    let mut remaining: Vec<usize> = signatures.iter().map(|sig| sig.n).collect();
+    let mut handles = Vec::new();
+    for (sig_index, addr) in multi.iter_matches_in(region.as_ptr() as usize, region.len()) {
+        if remaining[sig_index] == 0 {
+            continue;
+        }
+        remaining[sig_index] -= 1;
+
         println!("Hooking @ {addr:#X}");
-        hook_direct(addr, func, addr);
+        handles.push(hook_direct(addr, signatures[sig_index].func, addr));
     }
-    Ok(())
+    Ok(handles)
 }
 
 #[mlua::lua_module]
@@ -48,15 +112,88 @@ fn patch(lua: &Lua) -> LuaResult<LuaValue> {
     let text_address = text_section.get::<_, LuaInteger>("address")? as usize;
     let text_size = text_section.get::<_, LuaInteger>("size")? as usize;
 
-    hook_search(
+    let music_hooks = hook_search(
         unsafe { std::slice::from_raw_parts(text_address as *const u8, text_size) },
-        "8B4340 4533DB C1E802 A801 7516|4180F401 4180FC01 4C8D6310 750C C70361000000 EB04",
-        2,
-        on_hook,
+        &[HookSignature {
+            what: "8B4340 4533DB C1E802 A801 7516|4180F401 4180FC01 4C8D6310 750C C70361000000 EB04",
+            n: 2,
+            func: on_hook,
+        }],
     )
     .expect("Cannot find hook!");
 
-    Ok(LuaValue::Nil)
+    let exports = lua.create_table()?;
+    exports.set("find", lua.create_function(lua_find)?)?;
+    exports.set("find_all", lua.create_function(lua_find_all)?)?;
+    exports.set("rfind", lua.create_function(lua_rfind)?)?;
+    exports.set("rfind_all", lua.create_function(lua_rfind_all)?)?;
+    exports.set("unhook", lua.create_function(lua_unhook)?)?;
+    exports.set("unhook_all", lua.create_function(lua_unhook_all)?)?;
+    exports.set("music_hooks", music_hooks)?;
+
+    Ok(LuaValue::Table(exports))
+}
+
+/// Searches the `len` bytes of memory starting at `base` for `pattern`, and
+/// returns the address of the first match, or `nil` if there is none.
+fn lua_find(_lua: &Lua, (pattern, base, len): (String, LuaInteger, LuaInteger)) -> LuaResult<Option<usize>> {
+    let (base, len) = (base as usize, len as usize);
+    let query = memsearch::Query::build(&pattern).map_err(|e| LuaError::RuntimeError(e.to_string()))?;
+    let haystack = unsafe { std::slice::from_raw_parts(base as *const u8, len) };
+    Ok(query.matches_in_slice(haystack).next().map(|offset| base + offset))
+}
+
+/// Searches the `len` bytes of memory starting at `base` for `pattern`, and
+/// returns a table of the addresses of every match.
+fn lua_find_all(lua: &Lua, (pattern, base, len): (String, LuaInteger, LuaInteger)) -> LuaResult<LuaTable> {
+    let (base, len) = (base as usize, len as usize);
+    let query = memsearch::Query::build(&pattern).map_err(|e| LuaError::RuntimeError(e.to_string()))?;
+    let haystack = unsafe { std::slice::from_raw_parts(base as *const u8, len) };
+
+    let matches = lua.create_table()?;
+    for (i, offset) in query.matches_in_slice(haystack).enumerate() {
+        matches.set(i + 1, base + offset)?;
+    }
+    Ok(matches)
+}
+
+/// Searches the `len` bytes of memory starting at `base` for `pattern`, from
+/// the end backwards, and returns the address of the last match, or `nil`
+/// if there is none. Useful for locating the last occurrence of a prologue
+/// before some known landmark.
+fn lua_rfind(_lua: &Lua, (pattern, base, len): (String, LuaInteger, LuaInteger)) -> LuaResult<Option<usize>> {
+    let (base, len) = (base as usize, len as usize);
+    let query = memsearch::Query::build(&pattern).map_err(|e| LuaError::RuntimeError(e.to_string()))?;
+    let haystack = unsafe { std::slice::from_raw_parts(base as *const u8, len) };
+    Ok(query.rmatches_in_slice(haystack).next().map(|offset| base + offset))
+}
+
+/// Searches the `len` bytes of memory starting at `base` for `pattern`, and
+/// returns a table of the addresses of every match, ordered from the end of
+/// the range backwards.
+fn lua_rfind_all(lua: &Lua, (pattern, base, len): (String, LuaInteger, LuaInteger)) -> LuaResult<LuaTable> {
+    let (base, len) = (base as usize, len as usize);
+    let query = memsearch::Query::build(&pattern).map_err(|e| LuaError::RuntimeError(e.to_string()))?;
+    let haystack = unsafe { std::slice::from_raw_parts(base as *const u8, len) };
+
+    let matches = lua.create_table()?;
+    for (i, offset) in query.rmatches_in_slice(haystack).enumerate() {
+        matches.set(i + 1, base + offset)?;
+    }
+    Ok(matches)
+}
+
+/// Restores the original bytes at the hook identified by `handle`, as
+/// previously returned in `music_hooks`. Returns whether a hook with that
+/// handle was still installed.
+fn lua_unhook(_lua: &Lua, handle: LuaInteger) -> LuaResult<bool> {
+    Ok(unsafe { &mut HOOKS }.unhook(handle as usize))
+}
+
+/// Restores the original bytes at every hook installed so far.
+fn lua_unhook_all(_lua: &Lua, _: ()) -> LuaResult<()> {
+    unsafe { &mut HOOKS }.unhook_all();
+    Ok(())
 }
 
 unsafe extern "win64" fn on_hook(
@@ -1,5 +1,106 @@
 use core::slice;
+use std::collections::HashMap;
 use std::error::Error;
+use std::mem::size_of;
+
+/// Number of bytes in a machine word, used for SWAR scanning.
+const WORD_SIZE: usize = size_of::<usize>();
+/// A machine word with the low bit of every byte lane set.
+const LANE_LSB: usize = 0x0101010101010101;
+/// A machine word with the high bit of every byte lane set.
+const LANE_MSB: usize = 0x8080808080808080;
+
+/// Broadcasts a byte into every lane of a machine word.
+fn broadcast(byte: u8) -> usize {
+    byte as usize * LANE_LSB
+}
+
+/// Returns a word with only lane high bits set, nonzero exactly when `word`
+/// contains a zero byte. Every zero lane is flagged, and the lowest flagged
+/// lane is always a true zero; a lane holding 0x01 directly above a zero
+/// lane can be flagged as well, through the borrow.
+///
+/// This is the classic SWAR "has zero byte" trick: `w - 0x0101..01` borrows
+/// out of a zero lane into its high bit, `!w` masks out lanes that already
+/// had their high bit set for an unrelated reason, and `& 0x8080..80` keeps
+/// only the high bits.
+fn zero_lanes(word: usize) -> usize {
+    word.wrapping_sub(LANE_LSB) & !word & LANE_MSB
+}
+
+/// Iterates over addresses in `[start, end)` whose byte equals `target`,
+/// scanning a machine word at a time instead of one byte at a time.
+///
+/// Matches are found a word at a time via [`zero_lanes`], reading words
+/// unaligned from `start`; any tail shorter than a full word is scanned a
+/// byte at a time.
+struct ByteScanIter {
+    /// Address of the next byte/word to examine.
+    pos: usize,
+    /// Exclusive upper bound of the scan.
+    end: usize,
+    /// The byte value being searched for.
+    target: u8,
+    /// `target` broadcast into every lane, to XOR against loaded words.
+    needle: usize,
+    /// Matches found in the word starting at `pending_base` that have not
+    /// yet been yielded, as one set high bit per matching lane.
+    pending: usize,
+    /// Address of the word `pending` was computed from.
+    pending_base: usize,
+}
+
+impl ByteScanIter {
+    fn new(start: usize, end: usize, target: u8) -> Self {
+        Self {
+            pos: start,
+            end,
+            target,
+            needle: broadcast(target),
+            pending: 0,
+            pending_base: start,
+        }
+    }
+}
+
+impl Iterator for ByteScanIter {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.pending != 0 {
+                let addr = self.pending_base + (self.pending.trailing_zeros() as usize / 8);
+                // Clear the lowest set bit, i.e. the lane we just examined.
+                self.pending &= self.pending - 1;
+                // `zero_lanes` can flag a lane above a true match spuriously,
+                // so confirm the byte before yielding it.
+                if unsafe { *(addr as *const u8) } == self.target {
+                    return Some(addr);
+                }
+                continue;
+            }
+
+            if self.pos + WORD_SIZE > self.end {
+                // Fewer than a full word left: fall back to a scalar loop.
+                if self.pos >= self.end {
+                    return None;
+                }
+                let byte = unsafe { *(self.pos as *const u8) };
+                let addr = self.pos;
+                self.pos += 1;
+                if byte == self.target {
+                    return Some(addr);
+                }
+                continue;
+            }
+
+            let word = unsafe { (self.pos as *const usize).read_unaligned() };
+            self.pending = zero_lanes(word ^ self.needle);
+            self.pending_base = self.pos;
+            self.pos += WORD_SIZE;
+        }
+    }
+}
 
 /// Represents a masked byte.
 /// 
@@ -108,6 +209,16 @@ impl Query {
         self.bytes.len()
     }
 
+    /// Returns the offset and value of a fully concrete byte (`mask ==
+    /// 0xFF`) in this query, preferring the one nearest the start, or
+    /// `None` if the query has no concrete byte to anchor a fast scan on.
+    fn lead_byte(&self) -> Option<(usize, u8)> {
+        self.bytes.iter()
+            .enumerate()
+            .find(|(_, masked)| masked.mask == 0xFF)
+            .map(|(offset, masked)| (offset, masked.byte))
+    }
+
     /// Returns a query built from a query string.
     /// 
     /// # Arguments
@@ -165,22 +276,61 @@ impl Query {
         })
     }
 
-    /// Returns whether the query matches at the 
+    /// Returns whether the query matches at the
     pub fn does_match_at(&self, addr: usize) -> bool {
         let memory = unsafe { slice::from_raw_parts(addr as *const u8, self.len()) };
 
         *self.bytes == *memory
     }
 
+    /// Returns whether the query matches `haystack` at offset `pos`.
+    fn does_match_in_slice(&self, haystack: &[u8], pos: usize) -> bool {
+        match haystack.get(pos..pos + self.len()) {
+            Some(window) => *self.bytes == *window,
+            None => false,
+        }
+    }
+
+    /// Returns a safe iterator over matches of this query in `haystack`,
+    /// yielding the offset of each match (with `anchor` applied) into the
+    /// slice, from the start forward.
+    pub fn matches_in_slice<'a>(&'a self, haystack: &'a [u8]) -> impl Iterator<Item = usize> + 'a {
+        let last = haystack.len().saturating_sub(self.len());
+        (0..=last)
+            .filter(move |&pos| self.does_match_in_slice(haystack, pos))
+            .map(move |pos| pos + self.anchor)
+    }
+
+    /// Like [`Self::matches_in_slice`], but yields matches from the end of
+    /// `haystack` backwards, e.g. to find the last occurrence of a pattern
+    /// before some known landmark.
+    pub fn rmatches_in_slice<'a>(&'a self, haystack: &'a [u8]) -> impl Iterator<Item = usize> + 'a {
+        let last = haystack.len().saturating_sub(self.len());
+        (0..=last).rev()
+            .filter(move |&pos| self.does_match_in_slice(haystack, pos))
+            .map(move |pos| pos + self.anchor)
+    }
+
     /// Returns an iterator that iterates over query matches in memory range
     /// starting at address `start` and having length `len`.
     pub fn iter_matches_in(&self, start: usize, len: usize) -> QueryIter {
         // Calculate address of last possible byte where a match can begin.
         let end = start + len - self.len();
 
+        // If the query has a concrete byte, scan for it a word at a time
+        // and only verify the full pattern at each candidate it turns up.
+        // Otherwise every position is a candidate.
+        let addr_iter: Box<dyn Iterator<Item = usize>> = match self.lead_byte() {
+            Some((offset, byte)) => Box::new(
+                ByteScanIter::new(start + offset, end + offset + 1, byte)
+                    .map(move |addr| addr - offset)
+            ),
+            None => Box::new(start..=end),
+        };
+
         QueryIter {
             query: &self,
-            addr_iter: Box::from(start..=end),
+            addr_iter,
         }
     }
 
@@ -193,6 +343,168 @@ impl Query {
     }
 }
 
+/// A static, heuristic table scoring how common each byte value is in
+/// typical compiled code, indexed by byte value. Lower scores mean the byte
+/// is rarer, and thus a better selector byte to scan for.
+static BYTE_FREQUENCY: [u8; 256] = [
+    240, 170, 100, 160, 100, 100, 100, 100, 100, 100, 100, 100,  40,  40,   5, 200,
+    100, 100, 100, 100, 100, 100,   5,   5, 100, 100, 100, 100,  40,  40,   5,   5,
+    100, 100, 100, 100, 150, 150, 100,   5, 100, 100, 100, 100,  40,  40, 100,   5,
+    100, 100, 100, 100,  40,  40, 100,   5, 100, 150, 100, 150,  40,  40, 100,   5,
+    140, 160, 100, 100, 140, 140, 100, 100, 230, 180, 100, 100, 170, 160, 100, 100,
+    170, 165, 160, 165, 150, 180, 150, 150, 100, 100, 100, 100, 150, 170, 100, 100,
+      5,   5,  10, 100, 140, 100, 170, 100, 100, 100, 100, 100, 100, 100, 100, 100,
+    100, 100, 100, 100, 195, 195, 100, 100, 100, 100, 100, 100, 100, 100, 100, 100,
+    100, 100,   5, 205, 100, 180, 100, 100, 100, 225, 160, 225, 100, 185, 100, 100,
+    200, 100, 100, 100, 100, 100, 100, 100, 100, 100,   5,  20, 100, 100, 100, 100,
+    100, 100, 100, 100, 100, 100,  10,  10,  40,  40, 100, 100, 100, 100, 100, 100,
+    100, 100, 100, 100, 100, 100, 100, 100, 100, 100, 100, 100, 100, 100, 100, 100,
+    100, 100, 100, 215, 100, 100, 150, 175, 100, 100, 100, 100,  30,  20,   5, 100,
+    100, 100, 100, 100,   8,   8,   5, 100,  15,  15,  15,  15,  15,  15,  15,  15,
+    100, 100, 100, 100, 100, 100, 100, 100, 210, 190, 100, 200, 100, 100, 100, 100,
+    100,   5, 100, 100, 100, 100, 140, 140, 100, 100, 100, 100, 100, 100, 100, 235,
+];
+
+/// A single entry in a [`MultiQuery`]'s selector map: the index of the
+/// pattern this entry belongs to, and the offset of the selector byte
+/// within that pattern.
+type SelectorEntry = (usize, usize);
+
+/// Scans memory for matches against many queries at once.
+///
+/// Rather than scanning the same region once per query, `MultiQuery` picks a
+/// rare "selector" byte out of each query using [`BYTE_FREQUENCY`], groups
+/// queries by that byte, and scans the region a single time: at each
+/// position whose byte is a selector for one or more queries, those queries
+/// are verified at the appropriate offset with [`Query::does_match_at`].
+#[derive(Debug)]
+pub struct MultiQuery {
+    /// The queries to search for, in the order they were given to `build`.
+    queries: Box<[Query]>,
+    /// Maps a selector byte value to the queries that picked it, alongside
+    /// the offset of that byte within each query.
+    by_selector: HashMap<u8, Vec<SelectorEntry>>,
+    /// Indices into `queries` of patterns with no concrete byte at all,
+    /// which must be checked at every position instead.
+    unanchored: Vec<usize>,
+}
+
+impl MultiQuery {
+    /// Returns a `MultiQuery` built from several query strings.
+    ///
+    /// # Arguments
+    ///
+    /// * `whats` - The query strings to build queries from.
+    pub fn build(whats: &[&str]) -> Result<Self, Box<dyn Error>> {
+        let queries = whats.iter()
+            .map(|what| Query::build(what))
+            .collect::<Result<Vec<_>, _>>()?
+            .into_boxed_slice();
+
+        let mut by_selector: HashMap<u8, Vec<SelectorEntry>> = HashMap::new();
+        let mut unanchored = Vec::new();
+
+        for (pattern_index, query) in queries.iter().enumerate() {
+            match Self::selector_byte(query) {
+                Some((offset, byte)) => by_selector.entry(byte).or_default().push((pattern_index, offset)),
+                None => unanchored.push(pattern_index),
+            }
+        }
+
+        Ok(Self { queries, by_selector, unanchored })
+    }
+
+    /// Returns the offset and value of the rarest concrete byte in `query`,
+    /// or `None` if it has no concrete byte to select on.
+    fn selector_byte(query: &Query) -> Option<(usize, u8)> {
+        query.bytes.iter()
+            .enumerate()
+            .filter(|(_, masked)| masked.mask == 0xFF)
+            .min_by_key(|(_, masked)| BYTE_FREQUENCY[masked.byte as usize])
+            .map(|(offset, masked)| (offset, masked.byte))
+    }
+
+    /// Returns an iterator that iterates over matches for all queries in
+    /// memory range starting at address `start` and having length `len`.
+    ///
+    /// Each item is a `(pattern_index, addr)` pair identifying which query
+    /// matched and where.
+    pub fn iter_matches_in(&self, start: usize, len: usize) -> MultiQueryIter {
+        MultiQueryIter {
+            multi: self,
+            start,
+            len,
+            pos: start,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Executes all queries on memory range starting at address `start` and
+    /// having length `len`, and returns a boxed slice of `(pattern_index,
+    /// addr)` pairs for every match.
+    pub fn find_matches_in(&self, start: usize, len: usize) -> Box<[(usize, usize)]> {
+        self.iter_matches_in(start, len)
+            .collect::<Vec<_>>()
+            .into_boxed_slice()
+    }
+}
+
+/// Iterates over matches for a [`MultiQuery`] in an address range.
+pub struct MultiQueryIter<'a> {
+    multi: &'a MultiQuery,
+    start: usize,
+    len: usize,
+    /// Address of the next byte to examine.
+    pos: usize,
+    /// Matches found at a position already visited, not yet yielded.
+    pending: Vec<(usize, usize)>,
+}
+
+impl Iterator for MultiQueryIter<'_> {
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(entry) = self.pending.pop() {
+                return Some(entry);
+            }
+
+            if self.pos >= self.start + self.len {
+                return None;
+            }
+            let addr = self.pos;
+            self.pos += 1;
+
+            // Unanchored patterns are checked at every position.
+            for &pattern_index in &self.multi.unanchored {
+                let query = &self.multi.queries[pattern_index];
+                if addr + query.len() <= self.start + self.len && query.does_match_at(addr) {
+                    self.pending.push((pattern_index, addr + query.anchor));
+                }
+            }
+
+            // Anchored patterns are only checked where their selector byte
+            // turns up.
+            let byte = unsafe { *(addr as *const u8) };
+            if let Some(entries) = self.multi.by_selector.get(&byte) {
+                for &(pattern_index, offset) in entries {
+                    if addr < offset {
+                        continue;
+                    }
+                    let match_start = addr - offset;
+                    let query = &self.multi.queries[pattern_index];
+                    if match_start >= self.start
+                        && match_start + query.len() <= self.start + self.len
+                        && query.does_match_at(match_start)
+                    {
+                        self.pending.push((pattern_index, match_start + query.anchor));
+                    }
+                }
+            }
+        }
+    }
+}
+
 /// Searches the given memory range for any addresses matching the given query
 /// string, and returns all matches.
 pub fn find_in(what: &str, start: usize, len: usize) -> Result<Box<[usize]>, Box<dyn Error>> {
@@ -468,6 +780,15 @@ mod tests {
         );
     }
 
+    /// Tests executing query on a block of memory longer than a machine
+    /// word, where the match lies in the scalar tail of the SWAR scan.
+    #[test]
+    fn query_execute_ok_word_boundary() {
+        let mut data = vec![0u8; 17];
+        data[16] = 0xAB;
+        query_execute_helper("ab", &data, &[16]);
+    }
+
     /// Test finding the first n results with an invalid query string
     #[test]
     fn find_n_in_err() {
@@ -478,4 +799,80 @@ mod tests {
             Err(())
         );
     }
+
+    /// Tests matching a query against a borrowed slice, forwards.
+    #[test]
+    fn query_matches_in_slice_ok() {
+        let query = Query::build("34 56").unwrap();
+        let data: [u8; 8] = [0x12, 0x34, 0x56, 0x78, 0x12, 0x34, 0x56, 0x78];
+        let matches: Vec<_> = query.matches_in_slice(&data).collect();
+        assert_eq!(matches, vec![1, 5]);
+    }
+
+    /// Tests matching a query against a borrowed slice, in reverse.
+    #[test]
+    fn query_rmatches_in_slice_ok() {
+        let query = Query::build("34 56").unwrap();
+        let data: [u8; 8] = [0x12, 0x34, 0x56, 0x78, 0x12, 0x34, 0x56, 0x78];
+        let matches: Vec<_> = query.rmatches_in_slice(&data).collect();
+        assert_eq!(matches, vec![5, 1]);
+    }
+
+    /// Tests matching a query against a slice too short to ever match.
+    #[test]
+    fn query_matches_in_slice_too_small() {
+        let query = Query::build("34 56").unwrap();
+        let data: [u8; 1] = [0x34];
+        assert_eq!(query.matches_in_slice(&data).count(), 0);
+    }
+
+    /// Tests building a MultiQuery from several valid query strings.
+    #[test]
+    fn multiquery_build_ok() {
+        MultiQuery::build(&["12 34", "xx 56", "78 xx 9A"]).unwrap();
+    }
+
+    /// Tests building a MultiQuery with an invalid query string.
+    #[test]
+    fn multiquery_build_err() {
+        MultiQuery::build(&["12 34", "56 7"]).unwrap_err();
+    }
+
+    /// Tests finding matches for several non-overlapping queries in one pass.
+    #[test]
+    fn multiquery_execute_ok_simple() {
+        let multi = MultiQuery::build(&["12 34", "56 78"]).unwrap();
+        let data: [u8; 4] = [0x12, 0x34, 0x56, 0x78];
+        let start = data.as_ptr() as usize;
+
+        let mut matches = multi.find_matches_in(start, data.len()).to_vec();
+        matches.sort();
+        assert_eq!(matches, vec![(0, start), (1, start + 2)]);
+    }
+
+    /// Tests finding matches where multiple patterns share the same selector
+    /// byte and both match at the same address.
+    #[test]
+    fn multiquery_execute_ok_overlapping_patterns() {
+        let multi = MultiQuery::build(&["12 34", "xx 34"]).unwrap();
+        let data: [u8; 2] = [0x12, 0x34];
+        let start = data.as_ptr() as usize;
+
+        let mut matches = multi.find_matches_in(start, data.len()).to_vec();
+        matches.sort();
+        assert_eq!(matches, vec![(0, start), (1, start)]);
+    }
+
+    /// Tests finding matches for a query with no concrete byte, which must
+    /// fall back to being checked at every position.
+    #[test]
+    fn multiquery_execute_ok_unanchored() {
+        let multi = MultiQuery::build(&["xx xx", "12 34"]).unwrap();
+        let data: [u8; 2] = [0x12, 0x34];
+        let start = data.as_ptr() as usize;
+
+        let mut matches = multi.find_matches_in(start, data.len()).to_vec();
+        matches.sort();
+        assert_eq!(matches, vec![(0, start), (1, start)]);
+    }
 }
@@ -1,3 +1,6 @@
+use core::slice;
+use std::fmt;
+
 pub enum LCnbExe {}
 pub enum LCOverlayUnk {}
 pub enum LCOverlayPicture {}
@@ -80,6 +83,259 @@ impl GBAState {
     pub unsafe fn from_addr<'a>(addr: u64) -> &'a mut Self {
         &mut *(addr as *mut Self)
     }
+
+    /// Checks that an access of `access_len` bytes at `addr` fits within a
+    /// region of `region_len` bytes.
+    fn check_bounds(addr: u32, access_len: u32, region_len: u32) -> Result<(), MemFault> {
+        let fits = matches!(addr.checked_add(access_len), Some(end) if end <= region_len);
+        if !fits {
+            return Err(MemFault::OutOfBounds { addr, access_len, region_len });
+        }
+        Ok(())
+    }
+
+    /// Checks that a typed access of `access_len` bytes at `addr` fits
+    /// within a region of `region_len` bytes and is aligned to `access_len`.
+    fn check_access(addr: u32, access_len: u32, region_len: u32) -> Result<(), MemFault> {
+        Self::check_bounds(addr, access_len, region_len)?;
+        if addr % access_len != 0 {
+            return Err(MemFault::Misaligned { addr, align: access_len });
+        }
+        Ok(())
+    }
+
+    /// Like [`Self::read_u8`], but checks that `addr` lies within a region
+    /// of `region_len` bytes before dereferencing it.
+    pub fn try_read_u8(&self, addr: u32, region_len: u32) -> Result<u8, MemFault> {
+        Self::check_access(addr, 1, region_len)?;
+        Ok(self.read_u8(addr))
+    }
+    /// Like [`Self::read_u16`], but checks that `addr` lies within a region
+    /// of `region_len` bytes and is aligned before dereferencing it.
+    pub fn try_read_u16(&self, addr: u32, region_len: u32) -> Result<u16, MemFault> {
+        Self::check_access(addr, 2, region_len)?;
+        Ok(self.read_u16(addr))
+    }
+    /// Like [`Self::read_u32`], but checks that `addr` lies within a region
+    /// of `region_len` bytes and is aligned before dereferencing it.
+    pub fn try_read_u32(&self, addr: u32, region_len: u32) -> Result<u32, MemFault> {
+        Self::check_access(addr, 4, region_len)?;
+        Ok(self.read_u32(addr))
+    }
+    /// Like [`Self::write_u8`], but checks that `addr` lies within a region
+    /// of `region_len` bytes before dereferencing it.
+    pub fn try_write_u8(&self, addr: u32, val: u8, region_len: u32) -> Result<(), MemFault> {
+        Self::check_access(addr, 1, region_len)?;
+        self.write_u8(addr, val);
+        Ok(())
+    }
+    /// Like [`Self::write_u16`], but checks that `addr` lies within a region
+    /// of `region_len` bytes and is aligned before dereferencing it.
+    pub fn try_write_u16(&self, addr: u32, val: u16, region_len: u32) -> Result<(), MemFault> {
+        Self::check_access(addr, 2, region_len)?;
+        self.write_u16(addr, val);
+        Ok(())
+    }
+    /// Like [`Self::write_u32`], but checks that `addr` lies within a region
+    /// of `region_len` bytes and is aligned before dereferencing it.
+    pub fn try_write_u32(&self, addr: u32, val: u32, region_len: u32) -> Result<(), MemFault> {
+        Self::check_access(addr, 4, region_len)?;
+        self.write_u32(addr, val);
+        Ok(())
+    }
+
+    /// Returns a slice of `len` bytes of game memory starting at `addr`,
+    /// after checking that it lies within a region of `region_len` bytes.
+    /// Unlike the typed accessors, a byte slice has no alignment to check.
+    pub fn read_slice(&self, addr: u32, len: u32, region_len: u32) -> Result<&[u8], MemFault> {
+        Self::check_bounds(addr, len, region_len)?;
+        Ok(unsafe { slice::from_raw_parts(self.memory.offset(addr.try_into().unwrap()), len as usize) })
+    }
 }
 
+/// An error raised when a checked memory access on [`GBAState`] would read
+/// or write outside of a known memory region, or at an address unaligned
+/// for the access being performed.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MemFault {
+    /// The access would read or write outside of the known memory region.
+    OutOfBounds {
+        /// The address that was accessed.
+        addr: u32,
+        /// The size in bytes of the access.
+        access_len: u32,
+        /// The size in bytes of the known memory region.
+        region_len: u32,
+    },
+    /// The address does not satisfy the alignment required by the access.
+    Misaligned {
+        /// The address that was accessed.
+        addr: u32,
+        /// The required alignment in bytes.
+        align: u32,
+    },
+}
+impl fmt::Display for MemFault {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::OutOfBounds { addr, access_len, region_len } => write!(
+                f,
+                "access of {access_len} byte(s) at {addr:#X} is out of bounds of {region_len:#X}-byte region"
+            ),
+            Self::Misaligned { addr, align } => write!(
+                f,
+                "address {addr:#X} is not aligned to {align} byte(s)"
+            ),
+        }
+    }
+}
+impl std::error::Error for MemFault {}
+
 pub type GBAFunc = extern "C" fn(*mut GBAState) -> GBAFuncID;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a `GBAState` with `memory` pointing at `region` and every
+    /// other field zeroed, for exercising the checked accessors.
+    fn make_state(region: &mut [u8]) -> GBAState {
+        GBAState {
+            r0: 0, r1: 0, r2: 0, r3: 0, r4: 0, r5: 0, r6: 0, r7: 0,
+            r8: 0, r9: 0, r10: 0, r11: 0, r12: 0,
+            sp: 0,
+            lr: GBAFuncID(0),
+            pc: GBAFuncID(0),
+            flags: CPUFlags::NONE,
+            flags_implicit_update: CPUFlags::NONE,
+            memory: region.as_mut_ptr(),
+            stack_bottom: std::ptr::null_mut(),
+            owner: std::ptr::null(),
+            addr_ldmia_stmia: 0,
+            stack_count: 0,
+            call_depth: 0,
+            always1: 0,
+            is_alt_entry: false,
+            overlay_unk: std::ptr::null(),
+            overlay_bg: std::ptr::null(),
+            overlay_obj: std::ptr::null(),
+            obj_unk: std::ptr::null(),
+        }
+    }
+
+    /// Tests a checked u8 read within bounds.
+    #[test]
+    fn try_read_u8_ok() {
+        let mut region = vec![0x11u8, 0x22, 0x33, 0x44];
+        let gba = make_state(&mut region);
+        assert_eq!(gba.try_read_u8(2, region.len() as u32), Ok(0x33));
+    }
+
+    /// Tests a checked u8 read that runs past the end of the region.
+    #[test]
+    fn try_read_u8_out_of_bounds() {
+        let mut region = vec![0x11u8, 0x22, 0x33, 0x44];
+        let len = region.len() as u32;
+        let gba = make_state(&mut region);
+        assert_eq!(
+            gba.try_read_u8(4, len),
+            Err(MemFault::OutOfBounds { addr: 4, access_len: 1, region_len: 4 })
+        );
+    }
+
+    /// Tests a checked u16 read within bounds and aligned.
+    #[test]
+    fn try_read_u16_ok() {
+        let mut region = vec![0x11u8, 0x22, 0x33, 0x44];
+        let gba = make_state(&mut region);
+        assert_eq!(gba.try_read_u16(2, region.len() as u32), Ok(u16::from_ne_bytes([0x33, 0x44])));
+    }
+
+    /// Tests a checked u16 read at an address that is not 2-byte aligned.
+    #[test]
+    fn try_read_u16_misaligned() {
+        let mut region = vec![0x11u8, 0x22, 0x33, 0x44];
+        let len = region.len() as u32;
+        let gba = make_state(&mut region);
+        assert_eq!(
+            gba.try_read_u16(1, len),
+            Err(MemFault::Misaligned { addr: 1, align: 2 })
+        );
+    }
+
+    /// Tests a checked u32 read within bounds and aligned.
+    #[test]
+    fn try_read_u32_ok() {
+        let mut region = vec![0x11u8, 0x22, 0x33, 0x44];
+        let gba = make_state(&mut region);
+        assert_eq!(
+            gba.try_read_u32(0, region.len() as u32),
+            Ok(u32::from_ne_bytes([0x11, 0x22, 0x33, 0x44]))
+        );
+    }
+
+    /// Tests a checked u32 read that runs past the end of the region.
+    #[test]
+    fn try_read_u32_out_of_bounds() {
+        let mut region = vec![0x11u8, 0x22, 0x33, 0x44];
+        let len = region.len() as u32;
+        let gba = make_state(&mut region);
+        assert_eq!(
+            gba.try_read_u32(1, len),
+            Err(MemFault::OutOfBounds { addr: 1, access_len: 4, region_len: 4 })
+        );
+    }
+
+    /// Tests a checked u8 write within bounds.
+    #[test]
+    fn try_write_u8_ok() {
+        let mut region = vec![0x00u8; 4];
+        let len = region.len() as u32;
+        let gba = make_state(&mut region);
+        assert_eq!(gba.try_write_u8(1, 0xAB, len), Ok(()));
+        assert_eq!(gba.read_u8(1), 0xAB);
+    }
+
+    /// Tests a checked u32 write at an address that is not 4-byte aligned.
+    #[test]
+    fn try_write_u32_misaligned() {
+        let mut region = vec![0x00u8; 8];
+        let len = region.len() as u32;
+        let gba = make_state(&mut region);
+        assert_eq!(
+            gba.try_write_u32(2, 0xDEADBEEF, len),
+            Err(MemFault::Misaligned { addr: 2, align: 4 })
+        );
+    }
+
+    /// Tests reading a checked slice within bounds.
+    #[test]
+    fn read_slice_ok() {
+        let mut region = vec![0x11u8, 0x22, 0x33, 0x44];
+        let len = region.len() as u32;
+        let gba = make_state(&mut region);
+        assert_eq!(gba.read_slice(1, 2, len), Ok(&[0x22, 0x33][..]));
+    }
+
+    /// Tests reading a checked slice that runs past the end of the region.
+    #[test]
+    fn read_slice_out_of_bounds() {
+        let mut region = vec![0x11u8, 0x22, 0x33, 0x44];
+        let len = region.len() as u32;
+        let gba = make_state(&mut region);
+        assert_eq!(
+            gba.read_slice(3, 2, len),
+            Err(MemFault::OutOfBounds { addr: 3, access_len: 2, region_len: 4 })
+        );
+    }
+
+    /// Tests that a zero-length slice read at the very end of the region
+    /// succeeds instead of panicking on an alignment check.
+    #[test]
+    fn read_slice_zero_len_ok() {
+        let mut region = vec![0x11u8, 0x22, 0x33, 0x44];
+        let len = region.len() as u32;
+        let gba = make_state(&mut region);
+        assert_eq!(gba.read_slice(len, 0, len), Ok(&[][..]));
+    }
+}